@@ -4,6 +4,11 @@
 //! Written in Rust data structure, Sqlz provides simplest conversions
 //! from language domain models to JSON, and vice versa.
 
+// enum defaults are implemented by hand, `#[default]` needs Rust 1.62
+#![allow(clippy::derivable_impls)]
+
 pub mod model;
+#[cfg(feature = "parser")]
+pub mod parser;
 
 pub use model::*;
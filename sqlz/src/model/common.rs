@@ -1,3 +1,5 @@
+use std::convert::TryFrom;
+
 use serde::{Deserialize, Serialize};
 
 /// column key type
@@ -39,21 +41,69 @@ impl Default for ColumnType {
     }
 }
 
-/// a column mainly contains four arguments
+/// generated column, its value is computed from an expression, e.g. `price * qty`.
+/// stored: true for `STORED`, false for `VIRTUAL`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GeneratedColumn {
+    pub expression: String,
+    pub stored: bool,
+}
+
+/// a column mainly contains four arguments, and an optional collation
+/// (e.g. utf8mb4_unicode_ci, "C", NOCASE) for string columns.
+/// precision: fractional-second digits of Time/DateTime/Timestamp columns,
+/// e.g. 6 for MySQL `DATETIME(6)`, 3 for Postgres `timestamp(3)`
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Column {
     pub name: String,
     pub col_type: ColumnType,
     pub null: Option<bool>,
     pub key: Option<ColumnKey>,
+    pub collation: Option<String>,
+    pub precision: Option<u8>,
+    pub generated: Option<GeneratedColumn>,
+    pub comment: Option<String>,
 }
 
-/// table with its' name, columns and optional foreign key
+/// partition method
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum PartitionType {
+    Range,
+    List,
+}
+
+/// a partition contains a column name and its partition method
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Partition {
+    pub column: String,
+    pub partition_type: PartitionType,
+}
+
+/// table physical options, each of them only works on its own database:
+/// - engine & charset: MySQL, e.g. InnoDB & utf8mb4
+/// - fill_factor & cluster_index: Postgres
+/// - without_rowid: SQLite
+/// - collation: default collation of string columns
+/// - comment: table description
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TableOptions {
+    pub engine: Option<String>,
+    pub charset: Option<String>,
+    pub collation: Option<String>,
+    pub fill_factor: Option<u8>,
+    pub cluster_index: Option<String>,
+    pub without_rowid: Option<bool>,
+    pub comment: Option<String>,
+}
+
+/// table with its' name, columns, optional foreign key, partition and options
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
     pub foreign_key: Option<ForeignKey>,
+    pub partition: Option<Partition>,
+    pub options: Option<TableOptions>,
 }
 
 /// order type
@@ -70,12 +120,14 @@ pub struct Order {
     pub order: Option<OrderType>,
 }
 
-/// index with its' unique name, table belonged, and related index/ indices
+/// index with its' unique name, table belonged, and related index/ indices.
+/// full_text: create a full-text index, used by `Equation::Matches`
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Index {
     pub name: String,
     pub table: String,
     pub columns: Vec<Order>,
+    pub full_text: Option<bool>,
 }
 
 /// foreign key direction
@@ -111,11 +163,14 @@ pub struct ForeignKey {
     pub on_update: ForeignKeyAction,
 }
 
-/// schema indicates a database's tables (not in use)
+/// schema indicates a database's tables and indexes, a portable snapshot which can be
+/// exported as JSON from one database and applied to another
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Schema {
     pub schema: String,
     pub tables: Vec<Table>,
+    #[serde(default)]
+    pub indexes: Vec<Index>,
 }
 
 /// general data type
@@ -147,12 +202,48 @@ impl From<i32> for DataEnum {
     }
 }
 
+impl From<i64> for DataEnum {
+    fn from(v: i64) -> Self {
+        DataEnum::Integer(v)
+    }
+}
+
+impl From<u8> for DataEnum {
+    fn from(v: u8) -> Self {
+        DataEnum::Integer(v as i64)
+    }
+}
+
+impl From<u16> for DataEnum {
+    fn from(v: u16) -> Self {
+        DataEnum::Integer(v as i64)
+    }
+}
+
+impl From<u32> for DataEnum {
+    fn from(v: u32) -> Self {
+        DataEnum::Integer(v as i64)
+    }
+}
+
 impl From<f32> for DataEnum {
     fn from(v: f32) -> Self {
         DataEnum::Float(v as f64)
     }
 }
 
+impl From<f64> for DataEnum {
+    fn from(v: f64) -> Self {
+        DataEnum::Float(v)
+    }
+}
+
+impl From<bool> for DataEnum {
+    fn from(v: bool) -> Self {
+        DataEnum::Bool(v)
+    }
+}
+
 impl From<String> for DataEnum {
     fn from(v: String) -> Self {
         DataEnum::String(v)
@@ -165,19 +256,167 @@ impl From<&str> for DataEnum {
     }
 }
 
-/// column name, can be alias. used it in `select`
+impl From<&String> for DataEnum {
+    fn from(v: &String) -> Self {
+        DataEnum::String(v.to_owned())
+    }
+}
+
+/// `None` becomes `DataEnum::Null`
+impl<T: Into<DataEnum>> From<Option<T>> for DataEnum {
+    fn from(v: Option<T>) -> Self {
+        match v {
+            Some(v) => v.into(),
+            None => DataEnum::Null,
+        }
+    }
+}
+
+/// converting back from DataEnum, a mismatched variant is returned as the error
+impl TryFrom<DataEnum> for i64 {
+    type Error = DataEnum;
+
+    fn try_from(v: DataEnum) -> Result<Self, Self::Error> {
+        match v {
+            DataEnum::Integer(i) => Ok(i),
+            _ => Err(v),
+        }
+    }
+}
+
+/// integers are widened to float
+impl TryFrom<DataEnum> for f64 {
+    type Error = DataEnum;
+
+    fn try_from(v: DataEnum) -> Result<Self, Self::Error> {
+        match v {
+            DataEnum::Float(f) => Ok(f),
+            DataEnum::Integer(i) => Ok(i as f64),
+            _ => Err(v),
+        }
+    }
+}
+
+impl TryFrom<DataEnum> for String {
+    type Error = DataEnum;
+
+    fn try_from(v: DataEnum) -> Result<Self, Self::Error> {
+        match v {
+            DataEnum::String(s) => Ok(s),
+            _ => Err(v),
+        }
+    }
+}
+
+impl TryFrom<DataEnum> for bool {
+    type Error = DataEnum;
+
+    fn try_from(v: DataEnum) -> Result<Self, Self::Error> {
+        match v {
+            DataEnum::Bool(b) => Ok(b),
+            _ => Err(v),
+        }
+    }
+}
+
+/// functions available in `select` projections, rendered per database:
+/// - DateTrunc: `DATE_TRUNC` on Postgres, `DATE_FORMAT` on MySQL, `STRFTIME` on
+///   SQLite. the first argument is the unit, e.g. "day"
+/// - Round: the second argument is the number of decimal places
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Function {
+    Coalesce,
+    Lower,
+    Upper,
+    Round,
+    DateTrunc,
+}
+
+/// function argument, a column name or a literal value
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum FunctionArg {
+    Column(String),
+    Value(DataEnum),
+}
+
+/// a function call with its arguments, and an alias for the computed column
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FuncCall {
+    pub func: Function,
+    pub args: Vec<FunctionArg>,
+    pub alias: String,
+}
+
+/// window functions, `Lag`/`Lead` take a column and an optional offset
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum WindowFunction {
+    RowNumber,
+    Rank,
+    DenseRank,
+    Lag,
+    Lead,
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// window frame unit
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum FrameType {
+    Rows,
+    Range,
+}
+
+/// window frame boundary
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(u64),
+    CurrentRow,
+    Following(u64),
+    UnboundedFollowing,
+}
+
+/// window frame, `BETWEEN start AND end` when end is given
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WindowFrame {
+    pub frame_type: FrameType,
+    pub start: FrameBound,
+    pub end: Option<FrameBound>,
+}
+
+/// a window function call `func(args) OVER (PARTITION BY .. ORDER BY .. frame)`,
+/// and an alias for the computed column
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WindowFunc {
+    pub func: WindowFunction,
+    pub args: Vec<FunctionArg>,
+    pub partition_by: Vec<String>,
+    pub order_by: Vec<Order>,
+    pub frame: Option<WindowFrame>,
+    pub alias: String,
+}
+
+/// column name, can be alias, a window function or a function call. used it in `select`
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum ColumnAlias {
     Simple(String),
     Alias((String, String)),
+    Window(WindowFunc),
+    Func(FuncCall),
 }
 
 impl ColumnAlias {
+    /// original column name, function calls have none, their alias is used instead
     pub fn name(&self) -> String {
         match self {
             ColumnAlias::Simple(s) => s.to_owned(),
             ColumnAlias::Alias((s, _)) => s.to_owned(),
+            ColumnAlias::Window(w) => w.alias.to_owned(),
+            ColumnAlias::Func(f) => f.alias.to_owned(),
         }
     }
 }
@@ -211,4 +450,112 @@ mod tests_common {
 
         assert_eq!(deserialized, table);
     }
+
+    #[test]
+    fn schema_snapshot() {
+        let schema = Schema {
+            schema: "dev".to_string(),
+            tables: vec![Table {
+                name: "test".to_string(),
+                ..Default::default()
+            }],
+            indexes: vec![Index {
+                name: "idx_test_name".to_string(),
+                table: "test".to_string(),
+                columns: vec![Order {
+                    name: "name".to_string(),
+                    order: None,
+                }],
+                full_text: None,
+            }],
+        };
+
+        let serialized = serde_json::to_string(&schema).unwrap();
+
+        let deserialized: Schema = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized, schema);
+
+        // snapshots taken before indexes were recorded still load
+        let legacy: Schema = serde_json::from_str(r#"{"schema":"dev","tables":[]}"#).unwrap();
+
+        assert!(legacy.indexes.is_empty());
+    }
+
+    #[test]
+    fn data_enum_conversion() {
+        assert_eq!(DataEnum::from(7i64), DataEnum::Integer(7));
+        assert_eq!(DataEnum::from(7u32), DataEnum::Integer(7));
+        assert_eq!(DataEnum::from(true), DataEnum::Bool(true));
+
+        assert_eq!(i64::try_from(DataEnum::Integer(7)), Ok(7));
+        assert_eq!(f64::try_from(DataEnum::Integer(7)), Ok(7.0));
+        assert_eq!(String::try_from(DataEnum::from("z")), Ok("z".to_string()));
+        assert_eq!(bool::try_from(DataEnum::Null), Err(DataEnum::Null));
+
+        let name = "z".to_string();
+        let values: Vec<DataEnum> = vec![Some(1), None]
+            .into_iter()
+            .map(DataEnum::from)
+            .chain(vec![DataEnum::from(&name), DataEnum::from(None::<&str>)])
+            .collect();
+
+        assert_eq!(
+            values,
+            vec![
+                DataEnum::Integer(1),
+                DataEnum::Null,
+                DataEnum::from("z"),
+                DataEnum::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn func_call() {
+        let column = ColumnAlias::Func(FuncCall {
+            func: Function::Coalesce,
+            args: vec![
+                FunctionArg::Column("score".to_string()),
+                FunctionArg::Value(DataEnum::Integer(0)),
+            ],
+            alias: "score".to_string(),
+        });
+
+        let serialized = serde_json::to_string(&column).unwrap();
+
+        assert_eq!(
+            serialized,
+            r#"{"func":"Coalesce","args":[{"Column":"score"},{"Value":0}],"alias":"score"}"#
+        );
+
+        let deserialized: ColumnAlias = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized, column);
+    }
+
+    #[test]
+    fn window_func() {
+        let column = ColumnAlias::Window(WindowFunc {
+            func: WindowFunction::Sum,
+            args: vec![FunctionArg::Column("price".to_string())],
+            partition_by: vec!["ticker".to_string()],
+            order_by: vec![Order {
+                name: "date".to_string(),
+                order: Some(OrderType::Asc),
+            }],
+            frame: Some(WindowFrame {
+                frame_type: FrameType::Rows,
+                start: FrameBound::Preceding(4),
+                end: Some(FrameBound::CurrentRow),
+            }),
+            alias: "price_sum_5".to_string(),
+        });
+
+        let serialized = serde_json::to_string(&column).unwrap();
+        let deserialized: ColumnAlias = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized, column);
+        assert_eq!(column.name(), "price_sum_5");
+    }
 }
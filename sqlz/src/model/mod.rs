@@ -13,7 +13,9 @@ pub use schema::table_list::*;
 pub use schema::table_rename::*;
 pub use schema::table_truncate::*;
 
+pub use query::compound_select::*;
 pub use query::delete::*;
 pub use query::insert::*;
+pub use query::lineage::*;
 pub use query::select::*;
 pub use query::update::*;
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Order, Select};
+
+/// set operator between two selects
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum SetOperator {
+    Union,
+    UnionAll,
+    Intersect,
+    Except,
+}
+
+/// selects combined by set operators, e.g. `select UNION ALL select EXCEPT select`.
+/// operators are applied from left to right, `order`, `limit` and `offset`
+/// apply to the combined result
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CompoundSelect {
+    pub select: Select,
+    pub compounds: Vec<(SetOperator, Select)>,
+    pub order: Option<Vec<Order>>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests_compound_select {
+    use super::*;
+    use crate::ColumnAlias;
+
+    fn select(table: &str) -> Select {
+        Select {
+            table: table.to_owned(),
+            columns: vec![ColumnAlias::Simple("c1".to_owned())],
+            filter: None,
+            order: None,
+            limit: None,
+            offset: None,
+            with: None,
+        }
+    }
+
+    #[test]
+    fn compound() {
+        let compound = CompoundSelect {
+            select: select("current"),
+            compounds: vec![(SetOperator::UnionAll, select("archive"))],
+            order: None,
+            limit: Some(10),
+            offset: None,
+        };
+
+        let cvt = serde_json::to_string(&compound).unwrap();
+
+        let res = "{\"select\":{\"table\":\"current\",\"columns\":[\"c1\"],\"filter\":null,\"order\":null,\"limit\":null,\"offset\":null},\"compounds\":[[\"UnionAll\",{\"table\":\"archive\",\"columns\":[\"c1\"],\"filter\":null,\"order\":null,\"limit\":null,\"offset\":null}]],\"order\":null,\"limit\":10,\"offset\":null}";
+
+        assert_eq!(cvt, res);
+        assert_eq!(
+            serde_json::from_str::<CompoundSelect>(res).unwrap(),
+            compound
+        );
+    }
+}
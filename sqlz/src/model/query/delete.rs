@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Expression;
+
+/// delete statement, shares the same filter expressions as `Select`.
+/// deleting without a filter removes all rows
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Delete {
+    pub table: String,
+    pub filter: Option<Vec<Expression>>,
+}
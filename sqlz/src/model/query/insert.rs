@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+
+use crate::DataEnum;
+
+/// what to do when an inserted row conflicts with an existing key
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum InsertPolicy {
+    Fail,
+    IgnoreDuplicates,
+}
+
+impl Default for InsertPolicy {
+    fn default() -> Self {
+        InsertPolicy::Fail
+    }
+}
+
+/// how to align inserted columns to the target table's columns:
+/// - Strict: columns must be the same as the table's
+/// - Intersect: columns not in the table are dropped
+/// - FillMissing: as Intersect, and table columns not inserted are filled by the value
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum AlignColumns {
+    Strict,
+    Intersect,
+    FillMissing(DataEnum),
+}
+
+/// insert statement, each row of `values` follows the order of `columns`.
+/// `returning` lists generated columns (e.g. auto-increment ids) to be given back,
+/// in the same order as the inserted rows
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Insert {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub values: Vec<Vec<DataEnum>>,
+    pub returning: Option<Vec<String>>,
+    pub policy: Option<InsertPolicy>,
+    pub align: Option<AlignColumns>,
+}
+
+impl Insert {
+    /// align columns and values to the target table's columns by `align`,
+    /// return None if columns are duplicated, a row's length differs from `columns`,
+    /// `Strict` columns mismatch, or no column is left after aligning.
+    /// Nothing changes if `align` is None
+    pub fn aligned(mut self, table_columns: &[String]) -> Option<Self> {
+        let duplicated = self
+            .columns
+            .iter()
+            .enumerate()
+            .any(|(i, c)| self.columns[..i].contains(c));
+        if duplicated
+            || self
+                .values
+                .iter()
+                .any(|row| row.len() != self.columns.len())
+        {
+            return None;
+        }
+
+        let fill = match &self.align {
+            None => return Some(self),
+            Some(AlignColumns::Strict) => {
+                let mismatched = self.columns.len() != table_columns.len()
+                    || table_columns.iter().any(|c| !self.columns.contains(c));
+                return if mismatched { None } else { Some(self) };
+            }
+            Some(AlignColumns::Intersect) => None,
+            Some(AlignColumns::FillMissing(v)) => Some(v.clone()),
+        };
+
+        let kept: Vec<usize> = (0..self.columns.len())
+            .filter(|&i| table_columns.contains(&self.columns[i]))
+            .collect();
+        let missing: Vec<String> = match fill {
+            Some(_) => table_columns
+                .iter()
+                .filter(|c| !self.columns.contains(c))
+                .cloned()
+                .collect(),
+            None => vec![],
+        };
+
+        self.values = self
+            .values
+            .into_iter()
+            .map(|row| {
+                let mut row: Vec<DataEnum> = kept.iter().map(|&i| row[i].clone()).collect();
+                if let Some(v) = &fill {
+                    row.extend(missing.iter().map(|_| v.clone()));
+                }
+                row
+            })
+            .collect();
+        self.columns = kept
+            .iter()
+            .map(|&i| self.columns[i].clone())
+            .chain(missing)
+            .collect();
+
+        if self.columns.is_empty() {
+            return None;
+        }
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests_insert {
+    use super::*;
+
+    #[test]
+    fn insertion() {
+        let insertion = Insert {
+            table: "sqlz".to_owned(),
+            columns: vec!["name".to_owned(), "score".to_owned()],
+            values: vec![
+                vec![DataEnum::from("Jacob"), DataEnum::Integer(90)],
+                vec![DataEnum::from("Sam"), DataEnum::Null],
+            ],
+            returning: Some(vec!["id".to_owned()]),
+            policy: Some(InsertPolicy::IgnoreDuplicates),
+            align: None,
+        };
+
+        let cvt = serde_json::to_string(&insertion).unwrap();
+
+        let res = "{\"table\":\"sqlz\",\"columns\":[\"name\",\"score\"],\"values\":[[\"Jacob\",90],[\"Sam\",null]],\"returning\":[\"id\"],\"policy\":\"IgnoreDuplicates\",\"align\":null}";
+
+        assert_eq!(cvt, res);
+
+        let deserialized: Insert = serde_json::from_str(res).unwrap();
+
+        assert_eq!(deserialized, insertion);
+    }
+
+    #[test]
+    fn alignment() {
+        let insertion = Insert {
+            table: "sqlz".to_owned(),
+            columns: vec!["name".to_owned(), "extra".to_owned()],
+            values: vec![vec![DataEnum::from("Jacob"), DataEnum::Integer(1)]],
+            returning: None,
+            policy: None,
+            align: Some(AlignColumns::FillMissing(DataEnum::Integer(0))),
+        };
+        let table_columns = vec!["name".to_owned(), "score".to_owned()];
+
+        let aligned = insertion.clone().aligned(&table_columns).unwrap();
+
+        assert_eq!(aligned.columns, table_columns);
+        assert_eq!(
+            aligned.values,
+            vec![vec![DataEnum::from("Jacob"), DataEnum::Integer(0)]]
+        );
+
+        let strict = Insert {
+            align: Some(AlignColumns::Strict),
+            ..insertion.clone()
+        };
+
+        assert_eq!(strict.aligned(&table_columns), None);
+
+        for align in [
+            AlignColumns::Strict,
+            AlignColumns::Intersect,
+            AlignColumns::FillMissing(DataEnum::Null),
+        ] {
+            let duplicated = Insert {
+                columns: vec!["name".to_owned(), "name".to_owned()],
+                align: Some(align),
+                ..insertion.clone()
+            };
+
+            assert_eq!(duplicated.aligned(&table_columns), None);
+        }
+
+        let disjoint = Insert {
+            columns: vec!["a".to_owned(), "b".to_owned()],
+            align: Some(AlignColumns::Intersect),
+            ..insertion.clone()
+        };
+
+        assert_eq!(disjoint.aligned(&["x".to_owned()]), None);
+
+        let ragged = Insert {
+            values: vec![vec![DataEnum::from("Jacob")]],
+            ..insertion
+        };
+
+        assert_eq!(ragged.aligned(&table_columns), None);
+    }
+}
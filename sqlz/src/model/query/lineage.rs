@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ColumnAlias, CompoundSelect, Cte, CteQuery, FunctionArg, Select};
+
+/// a column of a source table
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SourceColumn {
+    pub table: String,
+    pub column: String,
+}
+
+/// an output column of a select, and the source table columns it derives from
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ColumnLineage {
+    pub column: String,
+    pub sources: Vec<SourceColumn>,
+}
+
+impl Select {
+    /// column-level lineage of this select, a window column also derives from its
+    /// partition and order columns. Columns from a common table expression are
+    /// traced back to the tables the cte reads from. A cte only sees the ctes defined
+    /// before it, unless the `WITH` is recursive, in which case it sees all of them and
+    /// its reference to itself adds no sources
+    pub fn lineage(&self) -> Vec<ColumnLineage> {
+        select_lineage(self, &[], &mut vec![])
+    }
+}
+
+// ctes visible to a select, each with the length of the scope prefix its own query sees
+type Scope<'a> = [(&'a Cte, usize)];
+
+fn select_lineage<'a>(
+    select: &'a Select,
+    scope: &Scope<'a>,
+    visiting: &mut Vec<&'a Cte>,
+) -> Vec<ColumnLineage> {
+    let mut scope = scope.to_vec();
+    if let Some(w) = &select.with {
+        let end = scope.len() + w.ctes.len();
+        for cte in w.ctes.iter() {
+            let visible = if w.recursive { end } else { scope.len() };
+            scope.push((cte, visible));
+        }
+    }
+
+    // only a recursive cte can see itself, so `visiting` is only hit by recursion
+    let cte_columns = match scope.iter().rev().find(|(c, _)| c.name == select.table) {
+        Some((cte, _)) if visiting.iter().any(|v| std::ptr::eq(*v, *cte)) => Some(vec![]),
+        Some((cte, visible)) => Some(cte_lineage(cte, &scope[..*visible], visiting)),
+        None => None,
+    };
+
+    let resolve = |column: &str| -> Vec<SourceColumn> {
+        match &cte_columns {
+            Some(cols) => cols
+                .iter()
+                .filter(|c| column == "*" || c.column == column)
+                .flat_map(|c| c.sources.clone())
+                .collect(),
+            None => vec![SourceColumn {
+                table: select.table.to_owned(),
+                column: column.to_owned(),
+            }],
+        }
+    };
+
+    select
+        .columns
+        .iter()
+        .map(|c| {
+            let (column, inputs) = match c {
+                ColumnAlias::Simple(s) => (s.to_owned(), vec![s.as_str()]),
+                ColumnAlias::Alias((s, a)) => (a.to_owned(), vec![s.as_str()]),
+                ColumnAlias::Window(w) => {
+                    // the partition and order columns decide the value as much as the args
+                    let mut inputs = arg_columns(&w.args);
+                    inputs.extend(w.partition_by.iter().map(|p| p.as_str()));
+                    inputs.extend(w.order_by.iter().map(|o| o.name.as_str()));
+                    (w.alias.to_owned(), inputs)
+                }
+                ColumnAlias::Func(f) => (f.alias.to_owned(), arg_columns(&f.args)),
+            };
+            let mut sources: Vec<SourceColumn> = vec![];
+            for s in inputs.into_iter().flat_map(&resolve) {
+                if !sources.contains(&s) {
+                    sources.push(s);
+                }
+            }
+            ColumnLineage { column, sources }
+        })
+        .collect()
+}
+
+fn cte_lineage<'a>(
+    cte: &'a Cte,
+    scope: &Scope<'a>,
+    visiting: &mut Vec<&'a Cte>,
+) -> Vec<ColumnLineage> {
+    visiting.push(cte);
+    let mut res = match &cte.query {
+        CteQuery::Select(s) => select_lineage(s, scope, visiting),
+        CteQuery::Compound(c) => compound_lineage(c, scope, visiting),
+    };
+    visiting.pop();
+
+    // explicit cte column names rename the query's columns by position
+    if let Some(names) = &cte.columns {
+        for (l, n) in res.iter_mut().zip(names) {
+            l.column = n.to_owned();
+        }
+    }
+    res
+}
+
+fn compound_lineage<'a>(
+    compound: &'a CompoundSelect,
+    scope: &Scope<'a>,
+    visiting: &mut Vec<&'a Cte>,
+) -> Vec<ColumnLineage> {
+    let mut res = select_lineage(&compound.select, scope, visiting);
+    // columns of combined selects match by position, names come from the first one
+    for (_, s) in compound.compounds.iter() {
+        for (l, other) in res.iter_mut().zip(select_lineage(s, scope, visiting)) {
+            for s in other.sources {
+                if !l.sources.contains(&s) {
+                    l.sources.push(s);
+                }
+            }
+        }
+    }
+    res
+}
+
+fn arg_columns(args: &[FunctionArg]) -> Vec<&str> {
+    args.iter()
+        .filter_map(|a| match a {
+            FunctionArg::Column(c) => Some(c.as_str()),
+            FunctionArg::Value(_) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_lineage {
+    use super::*;
+    use crate::{
+        DataEnum, FuncCall, Function, Order, SetOperator, WindowFunc, WindowFunction, With,
+    };
+
+    fn select(table: &str, columns: Vec<ColumnAlias>) -> Select {
+        Select {
+            table: table.to_owned(),
+            columns,
+            filter: None,
+            order: None,
+            limit: None,
+            offset: None,
+            with: None,
+        }
+    }
+
+    fn source(table: &str, column: &str) -> SourceColumn {
+        SourceColumn {
+            table: table.to_owned(),
+            column: column.to_owned(),
+        }
+    }
+
+    #[test]
+    fn lineage() {
+        let simple = ColumnAlias::Simple("id".to_owned());
+        let total = ColumnAlias::Func(FuncCall {
+            func: Function::Coalesce,
+            args: vec![
+                FunctionArg::Column("amount".to_owned()),
+                FunctionArg::Column("fallback".to_owned()),
+                FunctionArg::Value(DataEnum::Integer(0)),
+            ],
+            alias: "total".to_owned(),
+        });
+
+        let cte = Cte {
+            name: "paid".to_owned(),
+            columns: Some(vec!["pid".to_owned(), "total".to_owned()]),
+            query: CteQuery::Compound(CompoundSelect {
+                select: select("orders", vec![simple.clone(), total]),
+                compounds: vec![(
+                    SetOperator::UnionAll,
+                    select(
+                        "paid",
+                        vec![
+                            ColumnAlias::Simple("pid".to_owned()),
+                            ColumnAlias::Alias(("paid_amount".to_owned(), "t".to_owned())),
+                        ],
+                    ),
+                )],
+                order: None,
+                limit: None,
+                offset: None,
+            }),
+        };
+
+        let selection = Select {
+            with: Some(With {
+                recursive: true,
+                ctes: vec![cte],
+            }),
+            ..select(
+                "paid",
+                vec![
+                    ColumnAlias::Alias(("pid".to_owned(), "order_id".to_owned())),
+                    ColumnAlias::Simple("total".to_owned()),
+                    ColumnAlias::Simple("missing".to_owned()),
+                    ColumnAlias::Window(WindowFunc {
+                        func: WindowFunction::RowNumber,
+                        args: vec![],
+                        partition_by: vec!["pid".to_owned()],
+                        order_by: vec![Order {
+                            name: "total".to_owned(),
+                            order: None,
+                        }],
+                        frame: None,
+                        alias: "rn".to_owned(),
+                    }),
+                ],
+            )
+        };
+
+        assert_eq!(
+            selection.lineage(),
+            vec![
+                ColumnLineage {
+                    column: "order_id".to_owned(),
+                    sources: vec![source("orders", "id")],
+                },
+                ColumnLineage {
+                    column: "total".to_owned(),
+                    sources: vec![source("orders", "amount"), source("orders", "fallback")],
+                },
+                ColumnLineage {
+                    column: "missing".to_owned(),
+                    sources: vec![],
+                },
+                ColumnLineage {
+                    column: "rn".to_owned(),
+                    sources: vec![
+                        source("orders", "id"),
+                        source("orders", "amount"),
+                        source("orders", "fallback"),
+                    ],
+                },
+            ]
+        );
+
+        assert_eq!(
+            select("orders", vec![simple.clone()]).lineage(),
+            vec![ColumnLineage {
+                column: "id".to_owned(),
+                sources: vec![source("orders", "id")],
+            }]
+        );
+
+        // a non-recursive cte named after the table it reads from reads the table
+        let shadowing = Select {
+            with: Some(With {
+                recursive: false,
+                ctes: vec![Cte {
+                    name: "orders".to_owned(),
+                    columns: None,
+                    query: CteQuery::Select(select("orders", vec![simple.clone()])),
+                }],
+            }),
+            ..select("orders", vec![simple])
+        };
+
+        assert_eq!(
+            shadowing.lineage(),
+            vec![ColumnLineage {
+                column: "id".to_owned(),
+                sources: vec![source("orders", "id")],
+            }]
+        );
+
+        // a non-recursive cte cannot see the ctes defined after it
+        let x = ColumnAlias::Simple("x".to_owned());
+        let ordered = Select {
+            with: Some(With {
+                recursive: false,
+                ctes: vec![
+                    Cte {
+                        name: "a".to_owned(),
+                        columns: None,
+                        query: CteQuery::Select(select("b", vec![x.clone()])),
+                    },
+                    Cte {
+                        name: "b".to_owned(),
+                        columns: None,
+                        query: CteQuery::Select(select("src", vec![x.clone()])),
+                    },
+                ],
+            }),
+            ..select("a", vec![x])
+        };
+
+        assert_eq!(
+            ordered.lineage(),
+            vec![ColumnLineage {
+                column: "x".to_owned(),
+                sources: vec![source("b", "x")],
+            }]
+        );
+    }
+}
@@ -1,4 +1,6 @@
+pub mod compound_select;
 pub mod delete;
 pub mod insert;
+pub mod lineage;
 pub mod select;
 pub mod update;
@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{ColumnAlias, DataEnum, Order};
+use crate::{ColumnAlias, CompoundSelect, DataEnum, Order};
 
 // TODO: Join & GroupBy
 
+/// select statement, can be directly deserialized from a JSON request body.
+/// `with` defines common table expressions which `table` may refer to
+/// (omitted from JSON when not set)
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Select {
     pub table: String,
@@ -12,20 +15,60 @@ pub struct Select {
     pub order: Option<Vec<Order>>,
     pub limit: Option<u64>,
     pub offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with: Option<With>,
 }
 
+/// query of a common table expression, a recursive one is a compound select
+/// whose latter selects refer to the cte itself
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum CteQuery {
+    Select(Select),
+    Compound(CompoundSelect),
+}
+
+/// a named common table expression, with optional column names
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Cte {
+    pub name: String,
+    pub columns: Option<Vec<String>>,
+    pub query: CteQuery,
+}
+
+/// `WITH [RECURSIVE]` clause
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct With {
+    pub recursive: bool,
+    pub ctes: Vec<Cte>,
+}
+
+/// a single row of a select result
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct SelectResult(pub serde_json::value::Value);
 
+/// rows of a select result
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct SelectVecResult(pub Vec<SelectResult>);
 
+/// conjunction between two expressions
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum Conjunction {
     AND,
     OR,
 }
 
+/// equation applied on a column.
+/// `Like` is case-sensitive, `ILike` is case-insensitive (`ILIKE` on Postgres,
+/// `LOWER()` on both sides elsewhere), `Condition.mode` does not change them.
+/// Patterns are passed through as is, use `escape_like` on user-supplied strings;
+/// SQLite then needs `ESCAPE '\'`.
+/// `Matches` is a full-text search (tsvector `@@` on Postgres, `MATCH ... AGAINST`
+/// on MySQL, FTS5 `MATCH` on SQLite), the column needs a full-text index.
+/// `Regex` is `~` on Postgres and `REGEXP` on MySQL/SQLite (SQLite requires a
+/// loaded regexp extension).
+/// `Column` compares with another column of the same row, e.g. `updated_at > created_at`.
+/// `InSubquery` checks membership in the single column returned by a select
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum Equation {
     Equal(DataEnum),
@@ -37,20 +80,107 @@ pub enum Equation {
     In(Vec<DataEnum>),
     Between((DataEnum, DataEnum)),
     Like(String),
+    ILike(String),
+    Matches(String),
+    Regex(String),
+    IsNull,
+    IsNotNull,
+    Column((Comparison, ColumnOperand)),
+    InSubquery(Select),
+}
+
+/// comparison operator, used by `Equation::Column`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+}
+
+/// arithmetic operator
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// a column, optionally combined with a literal, e.g. `price * 2`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ColumnOperand {
+    pub column: String,
+    pub arithmetic: Option<(Arithmetic, DataEnum)>,
+}
+
+impl Equation {
+    /// turn `Equal(Null)` & `NotEqual(Null)` into `IsNull` & `IsNotNull`,
+    /// since `= NULL` never matches in SQL
+    pub fn null_aware(self) -> Self {
+        match self {
+            Equation::Equal(DataEnum::Null) => Equation::IsNull,
+            Equation::NotEqual(DataEnum::Null) => Equation::IsNotNull,
+            e => e,
+        }
+    }
+}
+
+/// escape `\`, `%` and `_` by `\`, so that a user-supplied string is matched
+/// literally inside a `Like`/`ILike` pattern, e.g. `format!("%{}%", escape_like(s))`.
+/// `\` is the default escape character on MySQL and Postgres only, SQLite has none,
+/// so the rendered SQLite statement must add `ESCAPE '\'` to the `LIKE`
+pub fn escape_like(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == '\\' || c == '%' || c == '_' {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+/// comparison mode of a condition's string values. `AccentInsensitive` is
+/// also case-insensitive, and relies on `unaccent` on Postgres.
+/// it applies to the value comparisons (`Equal` to `Between`) only: pattern
+/// equations carry their own case handling (`Like` vs `ILike`), `Regex` and
+/// `Matches` follow the database, so `mode` is ignored for them
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum ComparisonMode {
+    Exact,
+    CaseInsensitive,
+    AccentInsensitive,
 }
 
+/// a condition contains a column name and its equation, and an optional
+/// comparison mode (omitted from JSON when not set, which means `Exact`)
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Condition {
     pub column: String,
     pub equation: Equation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ComparisonMode>,
+}
+
+/// `EXISTS (select)`, or `NOT EXISTS (select)` when negated
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Exists {
+    pub exists: Select,
+    pub negated: bool,
 }
 
+/// filter expression, a simple condition, a conjunction, a nested expression
+/// or an exists subquery
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Expression {
     Conjunction(Conjunction),
     Simple(Condition),
     Nest(Vec<Expression>),
+    Exists(Exists),
 }
 
 #[cfg(test)]
@@ -63,22 +193,26 @@ mod tests_select {
             Expression::Simple(Condition {
                 column: "c1".to_owned(),
                 equation: Equation::Between((DataEnum::Integer(23), DataEnum::Integer(25))),
+                mode: None,
             }),
             Expression::Conjunction(Conjunction::OR),
             Expression::Simple(Condition {
                 column: "c2".to_owned(),
                 equation: Equation::Equal(DataEnum::Integer(1)),
+                mode: None,
             }),
             Expression::Conjunction(Conjunction::AND),
             Expression::Nest(vec![
                 Expression::Simple(Condition {
                     column: "c3".to_owned(),
                     equation: Equation::Greater(DataEnum::Integer(23)),
+                    mode: None,
                 }),
                 Expression::Conjunction(Conjunction::AND),
                 Expression::Simple(Condition {
                     column: "c4".to_owned(),
                     equation: Equation::In(vec![DataEnum::from("T1"), DataEnum::from("T2")]),
+                    mode: None,
                 }),
             ]),
         ];
@@ -92,6 +226,7 @@ mod tests_select {
             order: None,
             limit: Some(10),
             offset: Some(20),
+            with: None,
         };
 
         let cvt = serde_json::to_string(&selection).unwrap();
@@ -119,4 +254,207 @@ mod tests_select {
 
         assert_eq!(cvt, res);
     }
+
+    #[test]
+    fn deserialization() {
+        let body = r##"
+        {
+            "table": "sqlz",
+            "columns": ["c1", ["c2", "c2_t"]],
+            "filter": [
+                {"column": "c1", "equation": {"Between": [23, 25]}},
+                "OR",
+                [
+                    {"column": "c2", "equation": {"Equal": "z"}, "mode": "CaseInsensitive"},
+                    "AND",
+                    {"column": "c3", "equation": {"In": ["T1", 2.5, true, null]}}
+                ]
+            ],
+            "order": [{"name": "c1", "order": "Desc"}],
+            "limit": 10,
+            "offset": null
+        }"##;
+
+        let selection: Select = serde_json::from_str(body).unwrap();
+
+        let expected = Select {
+            table: "sqlz".to_owned(),
+            columns: vec![
+                ColumnAlias::Simple("c1".to_owned()),
+                ColumnAlias::Alias(("c2".to_owned(), "c2_t".to_owned())),
+            ],
+            filter: Some(vec![
+                Expression::Simple(Condition {
+                    column: "c1".to_owned(),
+                    equation: Equation::Between((DataEnum::Integer(23), DataEnum::Integer(25))),
+                    mode: None,
+                }),
+                Expression::Conjunction(Conjunction::OR),
+                Expression::Nest(vec![
+                    Expression::Simple(Condition {
+                        column: "c2".to_owned(),
+                        equation: Equation::Equal(DataEnum::from("z")),
+                        mode: Some(ComparisonMode::CaseInsensitive),
+                    }),
+                    Expression::Conjunction(Conjunction::AND),
+                    Expression::Simple(Condition {
+                        column: "c3".to_owned(),
+                        equation: Equation::In(vec![
+                            DataEnum::from("T1"),
+                            DataEnum::Float(2.5),
+                            DataEnum::Bool(true),
+                            DataEnum::Null,
+                        ]),
+                        mode: None,
+                    }),
+                ]),
+            ]),
+            order: Some(vec![Order {
+                name: "c1".to_owned(),
+                order: Some(crate::OrderType::Desc),
+            }]),
+            limit: Some(10),
+            offset: None,
+            with: None,
+        };
+
+        assert_eq!(selection, expected);
+
+        let cvt = serde_json::to_string(&selection).unwrap();
+        let roundtrip: Select = serde_json::from_str(&cvt).unwrap();
+
+        assert_eq!(roundtrip, selection);
+    }
+
+    #[test]
+    fn null_equation() {
+        assert_eq!(
+            Equation::Equal(DataEnum::Null).null_aware(),
+            Equation::IsNull
+        );
+        assert_eq!(
+            Equation::NotEqual(DataEnum::Null).null_aware(),
+            Equation::IsNotNull
+        );
+        assert_eq!(
+            Equation::Equal(DataEnum::Integer(1)).null_aware(),
+            Equation::Equal(DataEnum::Integer(1))
+        );
+
+        let cvt = serde_json::to_string(&Condition {
+            column: "c1".to_owned(),
+            equation: Equation::IsNull,
+            mode: None,
+        })
+        .unwrap();
+
+        assert_eq!(cvt, "{\"column\":\"c1\",\"equation\":\"IsNull\"}");
+    }
+
+    #[test]
+    fn column_equation() {
+        let condition = Condition {
+            column: "updated_at".to_owned(),
+            equation: Equation::Column((
+                Comparison::Greater,
+                ColumnOperand {
+                    column: "created_at".to_owned(),
+                    arithmetic: Some((Arithmetic::Add, DataEnum::Integer(1))),
+                },
+            )),
+            mode: None,
+        };
+
+        let cvt = serde_json::to_string(&condition).unwrap();
+
+        let res = "{\"column\":\"updated_at\",\"equation\":{\"Column\":[\"Greater\",{\"column\":\"created_at\",\"arithmetic\":[\"Add\",1]}]}}";
+
+        assert_eq!(cvt, res);
+        assert_eq!(serde_json::from_str::<Condition>(res).unwrap(), condition);
+    }
+
+    #[test]
+    fn subquery() {
+        let inner = Select {
+            table: "orders".to_owned(),
+            columns: vec![ColumnAlias::Simple("user_id".to_owned())],
+            filter: None,
+            order: None,
+            limit: None,
+            offset: None,
+            with: None,
+        };
+        let filter = vec![
+            Expression::Simple(Condition {
+                column: "id".to_owned(),
+                equation: Equation::InSubquery(inner.clone()),
+                mode: None,
+            }),
+            Expression::Conjunction(Conjunction::AND),
+            Expression::Exists(Exists {
+                exists: inner,
+                negated: true,
+            }),
+        ];
+
+        let cvt = serde_json::to_string(&filter).unwrap();
+        let deserialized: Vec<Expression> = serde_json::from_str(&cvt).unwrap();
+
+        assert_eq!(deserialized, filter);
+    }
+
+    #[test]
+    fn cte() {
+        let anchor = Select {
+            table: "category".to_owned(),
+            columns: vec![ColumnAlias::Simple("id".to_owned())],
+            filter: Some(vec![Expression::Simple(Condition {
+                column: "parent_id".to_owned(),
+                equation: Equation::IsNull,
+                mode: None,
+            })]),
+            order: None,
+            limit: None,
+            offset: None,
+            with: None,
+        };
+        let recursion = Select {
+            table: "tree".to_owned(),
+            filter: None,
+            ..anchor.clone()
+        };
+        let selection = Select {
+            table: "tree".to_owned(),
+            columns: vec![ColumnAlias::Simple("id".to_owned())],
+            filter: None,
+            order: None,
+            limit: None,
+            offset: None,
+            with: Some(With {
+                recursive: true,
+                ctes: vec![Cte {
+                    name: "tree".to_owned(),
+                    columns: Some(vec!["id".to_owned()]),
+                    query: CteQuery::Compound(CompoundSelect {
+                        select: anchor,
+                        compounds: vec![(crate::SetOperator::UnionAll, recursion)],
+                        order: None,
+                        limit: None,
+                        offset: None,
+                    }),
+                }],
+            }),
+        };
+
+        let cvt = serde_json::to_string(&selection).unwrap();
+        let deserialized: Select = serde_json::from_str(&cvt).unwrap();
+
+        assert_eq!(deserialized, selection);
+    }
+
+    #[test]
+    fn like_escape() {
+        assert_eq!(escape_like("100%_done\\"), "100\\%\\_done\\\\");
+        assert_eq!(escape_like("plain"), "plain");
+    }
 }
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::DataEnum;
+
+/// batch update by key, each row of `values` follows the order of `columns`.
+/// `columns` must contain `key`, rows are matched by it and only the rest of
+/// `columns` are updated
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Update {
+    pub table: String,
+    pub key: String,
+    pub columns: Vec<String>,
+    pub values: Vec<Vec<DataEnum>>,
+}
+
+impl Update {
+    /// columns to be updated, i.e. `columns` without `key`
+    pub fn updated_columns(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|c| **c != self.key)
+            .map(|c| c.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_update {
+    use super::*;
+
+    #[test]
+    fn update() {
+        let update = Update {
+            table: "sqlz".to_owned(),
+            key: "id".to_owned(),
+            columns: vec!["id".to_owned(), "score".to_owned()],
+            values: vec![
+                vec![DataEnum::Integer(1), DataEnum::Integer(90)],
+                vec![DataEnum::Integer(2), DataEnum::Null],
+            ],
+        };
+
+        let cvt = serde_json::to_string(&update).unwrap();
+
+        let res = "{\"table\":\"sqlz\",\"key\":\"id\",\"columns\":[\"id\",\"score\"],\"values\":[[1,90],[2,null]]}";
+
+        assert_eq!(cvt, res);
+        assert_eq!(update.updated_columns(), vec!["score"]);
+    }
+}
@@ -1,7 +1,73 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
+/// a column as listed by `information_schema.columns`.
+/// is_nullable: `'YES'` maps to `Some(true)` and `'NO'` to `Some(false)`, booleans
+/// are also accepted. None if the database does not report it
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ColumnSimpleList {
     pub column_name: String,
     pub data_type: String,
+    #[serde(default, deserialize_with = "yes_no")]
+    pub is_nullable: Option<bool>,
+    pub column_comment: Option<String>,
+}
+
+fn yes_no<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<bool>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum YesNo {
+        Bool(bool),
+        String(String),
+    }
+
+    match Option::<YesNo>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(YesNo::Bool(b)) => Ok(Some(b)),
+        Some(YesNo::String(s)) => match s.to_uppercase().as_str() {
+            "YES" => Ok(Some(true)),
+            "NO" => Ok(Some(false)),
+            _ => Err(serde::de::Error::custom(format!(
+                "expected YES or NO, found {}",
+                s
+            ))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests_column_list {
+
+    use super::*;
+
+    #[test]
+    fn test_is_nullable() {
+        let yes: ColumnSimpleList = serde_json::from_str(
+            r#"{"column_name":"a","data_type":"int","is_nullable":"YES","column_comment":null}"#,
+        )
+        .unwrap();
+        let no: ColumnSimpleList = serde_json::from_str(
+            r#"{"column_name":"a","data_type":"int","is_nullable":"NO","column_comment":null}"#,
+        )
+        .unwrap();
+        let boolean: ColumnSimpleList = serde_json::from_str(
+            r#"{"column_name":"a","data_type":"int","is_nullable":false,"column_comment":null}"#,
+        )
+        .unwrap();
+        let missing: ColumnSimpleList =
+            serde_json::from_str(r#"{"column_name":"a","data_type":"int","column_comment":null}"#)
+                .unwrap();
+
+        assert_eq!(yes.is_nullable, Some(true));
+        assert_eq!(no.is_nullable, Some(false));
+        assert_eq!(boolean.is_nullable, Some(false));
+        assert_eq!(missing.is_nullable, None);
+
+        // serialized as a boolean, which deserializes back
+        let serialized = serde_json::to_string(&yes).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<ColumnSimpleList>(&serialized).unwrap(),
+            yes
+        );
+    }
 }
@@ -1,11 +1,19 @@
 use serde::{Deserialize, Serialize};
 
-use super::super::Column;
+use super::super::{Column, DataEnum};
 
 pub type ColumnAdd = Column;
 
 pub type ColumnModify = Column;
 
+/// add a not null column to a non-empty table, existing rows are filled by `backfill`
+/// before the not null constraint is set
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ColumnAddWithBackfill {
+    pub column: ColumnAdd,
+    pub backfill: DataEnum,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ColumnRename {
     pub from_name: String,
@@ -20,6 +28,7 @@ pub struct ColumnDrop {
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum ColumnAlterCase {
     Add(ColumnAdd),
+    AddWithBackfill(ColumnAddWithBackfill),
     Modify(ColumnModify),
     Rename(ColumnRename),
     Drop(ColumnDrop),
@@ -1,3 +1,78 @@
 use super::super::Table;
 
 pub type TableCreate = Table;
+
+/// sort tables so that each one comes after the table its foreign key refers to,
+/// which is the creating order (reverse it for dropping). Otherwise keeps the given order.
+/// references to tables not in `tables` and to the table itself are ignored.
+/// return None if foreign keys form a cycle
+pub fn sort_tables_by_dependency(tables: &[TableCreate]) -> Option<Vec<&TableCreate>> {
+    let dependency = |t: &TableCreate| {
+        t.foreign_key
+            .as_ref()
+            .map(|fk| fk.to.table.to_owned())
+            .filter(|d| *d != t.name && tables.iter().any(|o| o.name == *d))
+    };
+
+    let mut sorted: Vec<&TableCreate> = Vec::with_capacity(tables.len());
+    let mut rest: Vec<&TableCreate> = tables.iter().collect();
+
+    while !rest.is_empty() {
+        let pos = rest.iter().position(|t| match dependency(t) {
+            Some(d) => sorted.iter().any(|s| s.name == d),
+            None => true,
+        })?;
+        sorted.push(rest.remove(pos));
+    }
+
+    Some(sorted)
+}
+
+#[cfg(test)]
+mod tests_table_create {
+
+    use super::*;
+    use crate::model::{ForeignKey, ForeignKeyAction, ForeignKeyDir};
+
+    fn table(name: &str, refer: Option<&str>) -> TableCreate {
+        TableCreate {
+            name: name.to_string(),
+            foreign_key: refer.map(|r| ForeignKey {
+                name: format!("fk_{}_{}", name, r),
+                from: ForeignKeyDir {
+                    table: name.to_string(),
+                    column: format!("{}_id", r),
+                },
+                to: ForeignKeyDir {
+                    table: r.to_string(),
+                    column: "id".to_string(),
+                },
+                on_delete: ForeignKeyAction::Cascade,
+                on_update: ForeignKeyAction::default(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sort_tables_by_dependency() {
+        let tables = vec![
+            table("order_item", Some("order")),
+            table("order", Some("user")),
+            table("user", None),
+            table("tag", Some("tag")),
+        ];
+
+        let sorted: Vec<&str> = sort_tables_by_dependency(&tables)
+            .unwrap()
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+
+        assert_eq!(sorted, vec!["user", "order", "order_item", "tag"]);
+
+        let cycle = vec![table("a", Some("b")), table("b", Some("a"))];
+
+        assert_eq!(sort_tables_by_dependency(&cycle), None);
+    }
+}
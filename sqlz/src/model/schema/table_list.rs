@@ -3,4 +3,5 @@ use serde::{Deserialize, Serialize};
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct TableSimpleList {
     pub table_name: String,
+    pub table_comment: Option<String>,
 }
@@ -0,0 +1,841 @@
+//! Parse a plain SQL `SELECT` string into `Select`, the reverse of what Sqlz
+//! consumers do. Only what `Select` can express is accepted: a single table,
+//! no joins, no grouping, and the equations, functions and window functions
+//! Sqlz defines; anything else is reported as `ParseError::Unsupported`.
+//!
+//! `normalize` and `fingerprint` work on any SQL statement, so that queries only
+//! differing in literal values can be grouped together.
+
+use std::fmt::Display;
+
+use sqlparser::ast;
+use sqlparser::dialect::keywords::Keyword;
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+use crate::{
+    Arithmetic, ColumnAlias, ColumnOperand, Comparison, CompoundSelect, Condition, Conjunction,
+    Cte, CteQuery, DataEnum, Equation, Exists, Expression, FrameBound, FrameType, FuncCall,
+    Function, FunctionArg, Order, OrderType, Select, SetOperator, WindowFrame, WindowFunc,
+    WindowFunction, With,
+};
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// parse error
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// the string is not valid SQL
+    Syntax(String),
+    /// valid SQL that `Select` cannot express
+    Unsupported(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Syntax(s) => write!(f, "syntax error: {}", s),
+            ParseError::Unsupported(s) => write!(f, "unsupported: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn unsupported<T>(what: impl Display) -> ParseResult<T> {
+    Err(ParseError::Unsupported(what.to_string()))
+}
+
+/// normalize a SQL string: literals become `?` (a list of them inside `IN (...)` or
+/// `VALUES (...)` collapses into a single `?`), placeholders like `$1` are kept,
+/// comments and trailing semicolons are dropped, keywords are uppercased,
+/// unquoted identifiers are lowercased, and tokens are separated by a single space
+pub fn normalize(sql: &str) -> ParseResult<String> {
+    let tokens = Tokenizer::new(&GenericDialect {}, sql)
+        .tokenize()
+        .map_err(|e| ParseError::Syntax(e.to_string()))?;
+
+    let mut res: Vec<String> = vec![];
+    // whether the last token ends an operand, after which `-` is a binary operator
+    let mut operand = false;
+    let mut negative = false;
+    // whether the last token is a `$` right before this one
+    let mut dollar = false;
+    // for each open parenthesis, whether it holds a list of values
+    let mut lists: Vec<bool> = vec![];
+    // whether the last closed parenthesis held a list, e.g. the first row of `VALUES`
+    let mut closed_list = false;
+    for t in tokens {
+        let placeholder = std::mem::replace(&mut dollar, t == Token::Char('$'));
+        if let Token::Whitespace(_) | Token::EOF = t {
+            continue;
+        }
+        let ends_operand = match &t {
+            Token::Number(..)
+            | Token::SingleQuotedString(_)
+            | Token::NationalStringLiteral(_)
+            | Token::HexStringLiteral(_)
+            | Token::RParen => true,
+            Token::Word(w) => {
+                w.quote_style.is_some()
+                    || matches!(
+                        w.keyword,
+                        Keyword::NoKeyword | Keyword::TRUE | Keyword::FALSE | Keyword::NULL
+                    )
+            }
+            _ => false,
+        };
+        let minus = t == Token::Minus;
+
+        let s = match t {
+            Token::Number(n, _) if placeholder => {
+                res.pop();
+                format!("${}", n)
+            }
+            // a minus sign after an operator, `(`, `,` or a keyword is part of the number
+            Token::Number(..) if negative => {
+                res.pop();
+                "?".to_owned()
+            }
+            Token::Number(..)
+            | Token::SingleQuotedString(_)
+            | Token::NationalStringLiteral(_)
+            | Token::HexStringLiteral(_) => "?".to_owned(),
+            Token::Word(w) if w.quote_style.is_none() => match w.keyword {
+                Keyword::TRUE | Keyword::FALSE => "?".to_owned(),
+                Keyword::NoKeyword => w.value.to_lowercase(),
+                _ => w.value.to_uppercase(),
+            },
+            t => t.to_string(),
+        };
+        negative = minus && !operand;
+        operand = ends_operand;
+
+        let n = res.len();
+        match s.as_str() {
+            "(" => {
+                let prev = res.last().map(|p| p.as_str());
+                lists.push(
+                    matches!(prev, Some("IN") | Some("VALUES"))
+                        || (prev == Some(",") && closed_list),
+                );
+            }
+            ")" => closed_list = lists.pop().unwrap_or(false),
+            "," => {}
+            _ => closed_list = false,
+        }
+        let in_list = lists.last().copied().unwrap_or(false);
+        if s == "?" && in_list && n >= 2 && res[n - 1] == "," && res[n - 2] == "?" {
+            res.pop();
+            continue;
+        }
+        res.push(s);
+    }
+    while res.last().map(|s| s == ";").unwrap_or(false) {
+        res.pop();
+    }
+
+    Ok(res.join(" "))
+}
+
+/// fingerprint of the normalized SQL string (64-bit FNV-1a), stable across
+/// processes and builds
+pub fn fingerprint(sql: &str) -> ParseResult<u64> {
+    let hash = normalize(sql)?.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+
+    Ok(hash)
+}
+
+/// parse a single `SELECT` statement into `Select`
+pub fn parse_select(sql: &str) -> ParseResult<Select> {
+    let mut statements = Parser::parse_sql(&GenericDialect {}, sql)
+        .map_err(|e| ParseError::Syntax(e.to_string()))?;
+
+    if statements.len() != 1 {
+        return unsupported("exactly one statement is expected");
+    }
+    match statements.remove(0) {
+        ast::Statement::Query(q) => query_to_select(*q),
+        s => unsupported(s),
+    }
+}
+
+fn query_to_select(query: ast::Query) -> ParseResult<Select> {
+    let ast::Query {
+        with,
+        body,
+        order_by,
+        limit,
+        offset,
+        fetch,
+    } = query;
+
+    if fetch.is_some() {
+        return unsupported("FETCH");
+    }
+    let s = match body {
+        ast::SetExpr::Select(s) => *s,
+        ast::SetExpr::Query(q)
+            if with.is_none() && order_by.is_empty() && limit.is_none() && offset.is_none() =>
+        {
+            return query_to_select(*q)
+        }
+        b => return unsupported(b),
+    };
+
+    let mut select = plain_select(s)?;
+    select.order = orders(order_by)?;
+    select.limit = limit.map(count).transpose()?;
+    select.offset = offset.map(|o| count(o.value)).transpose()?;
+    select.with = with.map(with_clause).transpose()?;
+
+    Ok(select)
+}
+
+fn query_to_compound(query: ast::Query) -> ParseResult<CompoundSelect> {
+    if query.with.is_some() || query.fetch.is_some() {
+        return unsupported(query);
+    }
+
+    let mut selects = vec![];
+    set_expr_selects(query.body, None, &mut selects)?;
+    let mut selects = selects.into_iter();
+    let (_, select) = selects.next().unwrap();
+
+    Ok(CompoundSelect {
+        select,
+        compounds: selects.map(|(op, s)| (op.unwrap(), s)).collect(),
+        order: orders(query.order_by)?,
+        limit: query.limit.map(count).transpose()?,
+        offset: query.offset.map(|o| count(o.value)).transpose()?,
+    })
+}
+
+// flatten a left-deep set operation tree into selects combined from left to right
+fn set_expr_selects(
+    expr: ast::SetExpr,
+    op: Option<SetOperator>,
+    res: &mut Vec<(Option<SetOperator>, Select)>,
+) -> ParseResult<()> {
+    match expr {
+        ast::SetExpr::Select(s) => {
+            res.push((op, plain_select(*s)?));
+            Ok(())
+        }
+        ast::SetExpr::Query(q) => {
+            res.push((op, query_to_select(*q)?));
+            Ok(())
+        }
+        ast::SetExpr::SetOperation {
+            op: set_op,
+            all,
+            left,
+            right,
+        } => {
+            set_expr_selects(*left, op, res)?;
+            let set_op = match (set_op, all) {
+                (ast::SetOperator::Union, false) => SetOperator::Union,
+                (ast::SetOperator::Union, true) => SetOperator::UnionAll,
+                (ast::SetOperator::Intersect, false) => SetOperator::Intersect,
+                (ast::SetOperator::Except, false) => SetOperator::Except,
+                (o, _) => return unsupported(format!("{} ALL", o)),
+            };
+            match *right {
+                ast::SetExpr::SetOperation { .. } => unsupported("nested set operation"),
+                r => set_expr_selects(r, Some(set_op), res),
+            }
+        }
+        e => unsupported(e),
+    }
+}
+
+fn with_clause(with: ast::With) -> ParseResult<With> {
+    let ctes = with
+        .cte_tables
+        .into_iter()
+        .map(|c| {
+            if c.from.is_some() {
+                return unsupported(c);
+            }
+            let columns = match c.alias.columns.is_empty() {
+                true => None,
+                false => Some(c.alias.columns.into_iter().map(|i| i.value).collect()),
+            };
+            let query = match c.query.body {
+                ast::SetExpr::SetOperation { .. } => {
+                    CteQuery::Compound(query_to_compound(c.query)?)
+                }
+                _ => CteQuery::Select(query_to_select(c.query)?),
+            };
+            Ok(Cte {
+                name: c.alias.name.value,
+                columns,
+                query,
+            })
+        })
+        .collect::<ParseResult<Vec<_>>>()?;
+
+    Ok(With {
+        recursive: with.recursive,
+        ctes,
+    })
+}
+
+fn plain_select(s: ast::Select) -> ParseResult<Select> {
+    if s.distinct
+        || s.top.is_some()
+        || !s.lateral_views.is_empty()
+        || !s.group_by.is_empty()
+        || !s.cluster_by.is_empty()
+        || !s.distribute_by.is_empty()
+        || !s.sort_by.is_empty()
+        || s.having.is_some()
+    {
+        return unsupported("DISTINCT, TOP, GROUP BY, HAVING and Hive clauses");
+    }
+
+    let mut from = s.from;
+    if from.len() != 1 || !from[0].joins.is_empty() {
+        return unsupported("select from multiple tables");
+    }
+    let table = match from.remove(0).relation {
+        ast::TableFactor::Table {
+            name,
+            alias: None,
+            args,
+            with_hints,
+        } if args.is_empty() && with_hints.is_empty() => object_name(name),
+        r => return unsupported(r),
+    };
+
+    let columns = s
+        .projection
+        .into_iter()
+        .map(column)
+        .collect::<ParseResult<Vec<_>>>()?;
+    // an unaliased function is named after itself, which must not collide with another column
+    let output = |c: &ColumnAlias| match c {
+        ColumnAlias::Alias((_, a)) => a.to_owned(),
+        c => c.name(),
+    };
+    for c in columns.iter() {
+        if let ColumnAlias::Window(_) | ColumnAlias::Func(_) = c {
+            let name = output(c);
+            if columns.iter().filter(|o| output(o) == name).count() > 1 {
+                return unsupported(format!("duplicate output column `{}`", name));
+            }
+        }
+    }
+
+    Ok(Select {
+        table,
+        columns,
+        filter: s.selection.map(filter).transpose()?,
+        order: None,
+        limit: None,
+        offset: None,
+        with: None,
+    })
+}
+
+fn object_name(name: ast::ObjectName) -> String {
+    name.0
+        .into_iter()
+        .map(|i| i.value)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn column(item: ast::SelectItem) -> ParseResult<ColumnAlias> {
+    match item {
+        ast::SelectItem::Wildcard => Ok(ColumnAlias::Simple("*".to_owned())),
+        ast::SelectItem::UnnamedExpr(ast::Expr::Identifier(i)) => Ok(ColumnAlias::Simple(i.value)),
+        ast::SelectItem::ExprWithAlias {
+            expr: ast::Expr::Identifier(i),
+            alias,
+        } => Ok(ColumnAlias::Alias((i.value, alias.value))),
+        ast::SelectItem::UnnamedExpr(ast::Expr::Function(f)) => {
+            let alias = object_name(f.name.clone()).to_lowercase();
+            function(f, alias)
+        }
+        ast::SelectItem::ExprWithAlias {
+            expr: ast::Expr::Function(f),
+            alias,
+        } => function(f, alias.value),
+        i => unsupported(i),
+    }
+}
+
+fn function(f: ast::Function, alias: String) -> ParseResult<ColumnAlias> {
+    if f.distinct {
+        return unsupported(f);
+    }
+    let name = object_name(f.name.clone()).to_uppercase();
+    let args = f
+        .args
+        .into_iter()
+        .map(|a| match a {
+            ast::FunctionArg::Unnamed(ast::Expr::Identifier(i)) => Ok(FunctionArg::Column(i.value)),
+            ast::FunctionArg::Unnamed(ast::Expr::Wildcard) => {
+                Ok(FunctionArg::Column("*".to_owned()))
+            }
+            ast::FunctionArg::Unnamed(e) => Ok(FunctionArg::Value(value(e)?)),
+            a => unsupported(a),
+        })
+        .collect::<ParseResult<Vec<_>>>()?;
+
+    let over = match f.over {
+        Some(over) => over,
+        None => {
+            let func = match name.as_str() {
+                "COALESCE" => Function::Coalesce,
+                "LOWER" => Function::Lower,
+                "UPPER" => Function::Upper,
+                "ROUND" => Function::Round,
+                "DATE_TRUNC" => Function::DateTrunc,
+                _ => return unsupported(format!("function {}", name)),
+            };
+            return Ok(ColumnAlias::Func(FuncCall { func, args, alias }));
+        }
+    };
+
+    let func = match name.as_str() {
+        "ROW_NUMBER" => WindowFunction::RowNumber,
+        "RANK" => WindowFunction::Rank,
+        "DENSE_RANK" => WindowFunction::DenseRank,
+        "LAG" => WindowFunction::Lag,
+        "LEAD" => WindowFunction::Lead,
+        "COUNT" => WindowFunction::Count,
+        "SUM" => WindowFunction::Sum,
+        "AVG" => WindowFunction::Avg,
+        "MIN" => WindowFunction::Min,
+        "MAX" => WindowFunction::Max,
+        _ => return unsupported(format!("window function {}", name)),
+    };
+    let partition_by = over
+        .partition_by
+        .into_iter()
+        .map(|e| match e {
+            ast::Expr::Identifier(i) => Ok(i.value),
+            e => unsupported(e),
+        })
+        .collect::<ParseResult<Vec<_>>>()?;
+    let frame = over
+        .window_frame
+        .map(|w| {
+            let frame_type = match w.units {
+                ast::WindowFrameUnits::Rows => FrameType::Rows,
+                ast::WindowFrameUnits::Range => FrameType::Range,
+                u => return unsupported(u),
+            };
+            Ok(WindowFrame {
+                frame_type,
+                start: frame_bound(w.start_bound),
+                end: w.end_bound.map(frame_bound),
+            })
+        })
+        .transpose()?;
+
+    Ok(ColumnAlias::Window(WindowFunc {
+        func,
+        args,
+        partition_by,
+        order_by: orders(over.order_by)?.unwrap_or_default(),
+        frame,
+        alias,
+    }))
+}
+
+fn frame_bound(bound: ast::WindowFrameBound) -> FrameBound {
+    match bound {
+        ast::WindowFrameBound::CurrentRow => FrameBound::CurrentRow,
+        ast::WindowFrameBound::Preceding(None) => FrameBound::UnboundedPreceding,
+        ast::WindowFrameBound::Preceding(Some(n)) => FrameBound::Preceding(n),
+        ast::WindowFrameBound::Following(None) => FrameBound::UnboundedFollowing,
+        ast::WindowFrameBound::Following(Some(n)) => FrameBound::Following(n),
+    }
+}
+
+fn orders(order_by: Vec<ast::OrderByExpr>) -> ParseResult<Option<Vec<Order>>> {
+    if order_by.is_empty() {
+        return Ok(None);
+    }
+    order_by
+        .into_iter()
+        .map(|o| match (o.expr, o.nulls_first) {
+            (ast::Expr::Identifier(i), None) => Ok(Order {
+                name: i.value,
+                order: o.asc.map(|asc| match asc {
+                    true => OrderType::Asc,
+                    false => OrderType::Desc,
+                }),
+            }),
+            (e, _) => unsupported(e),
+        })
+        .collect::<ParseResult<Vec<_>>>()
+        .map(Some)
+}
+
+fn count(expr: ast::Expr) -> ParseResult<u64> {
+    match expr {
+        ast::Expr::Value(ast::Value::Number(n, _)) => match n.parse() {
+            Ok(n) => Ok(n),
+            Err(_) => unsupported(n),
+        },
+        e => unsupported(e),
+    }
+}
+
+fn value(expr: ast::Expr) -> ParseResult<DataEnum> {
+    match expr {
+        ast::Expr::Value(v) => match v {
+            ast::Value::Number(n, _) => number(n),
+            ast::Value::SingleQuotedString(s) => Ok(DataEnum::String(s)),
+            ast::Value::Boolean(b) => Ok(DataEnum::Bool(b)),
+            ast::Value::Null => Ok(DataEnum::Null),
+            v => unsupported(v),
+        },
+        ast::Expr::UnaryOp {
+            op: ast::UnaryOperator::Minus,
+            expr,
+        } => match *expr {
+            // negate the literal text, so that i64::MIN stays an integer
+            ast::Expr::Value(ast::Value::Number(n, _)) => number(format!("-{}", n)),
+            e => match value(e)? {
+                DataEnum::Integer(i) => match i.checked_neg() {
+                    Some(i) => Ok(DataEnum::Integer(i)),
+                    None => unsupported(format!("-({})", i)),
+                },
+                DataEnum::Float(f) => Ok(DataEnum::Float(-f)),
+                v => unsupported(format!("-{:?}", v)),
+            },
+        },
+        e => unsupported(e),
+    }
+}
+
+fn number(n: String) -> ParseResult<DataEnum> {
+    match n.parse::<i64>() {
+        Ok(i) => Ok(DataEnum::Integer(i)),
+        Err(_) => match n.parse::<f64>() {
+            Ok(f) => Ok(DataEnum::Float(f)),
+            Err(_) => unsupported(n),
+        },
+    }
+}
+
+fn string(expr: ast::Expr) -> ParseResult<String> {
+    match value(expr)? {
+        DataEnum::String(s) => Ok(s),
+        v => unsupported(format!("{:?} as a pattern", v)),
+    }
+}
+
+fn filter(expr: ast::Expr) -> ParseResult<Vec<Expression>> {
+    match expr {
+        ast::Expr::BinaryOp {
+            left,
+            op: op @ (ast::BinaryOperator::And | ast::BinaryOperator::Or),
+            right,
+        } => {
+            let conjunction = match op {
+                ast::BinaryOperator::And => Conjunction::AND,
+                _ => Conjunction::OR,
+            };
+            let mut res = filter(*left)?;
+            res.push(Expression::Conjunction(conjunction));
+            res.extend(filter(*right)?);
+            Ok(res)
+        }
+        ast::Expr::Nested(e) => match *e {
+            e @ ast::Expr::BinaryOp {
+                op: ast::BinaryOperator::And | ast::BinaryOperator::Or,
+                ..
+            } => Ok(vec![Expression::Nest(filter(e)?)]),
+            e => filter(e),
+        },
+        ast::Expr::Exists(q) => Ok(vec![Expression::Exists(Exists {
+            exists: query_to_select(*q)?,
+            negated: false,
+        })]),
+        ast::Expr::UnaryOp {
+            op: ast::UnaryOperator::Not,
+            expr,
+        } => match *expr {
+            ast::Expr::Exists(q) => Ok(vec![Expression::Exists(Exists {
+                exists: query_to_select(*q)?,
+                negated: true,
+            })]),
+            e => unsupported(format!("NOT {}", e)),
+        },
+        e => condition(e).map(|c| vec![Expression::Simple(c)]),
+    }
+}
+
+fn identifier(expr: ast::Expr) -> ParseResult<String> {
+    match expr {
+        ast::Expr::Identifier(i) => Ok(i.value),
+        e => unsupported(format!("{} as a column", e)),
+    }
+}
+
+fn condition(expr: ast::Expr) -> ParseResult<Condition> {
+    let (column, equation) = match expr {
+        ast::Expr::IsNull(e) => (identifier(*e)?, Equation::IsNull),
+        ast::Expr::IsNotNull(e) => (identifier(*e)?, Equation::IsNotNull),
+        ast::Expr::InList {
+            expr,
+            list,
+            negated: false,
+        } => (
+            identifier(*expr)?,
+            Equation::In(list.into_iter().map(value).collect::<ParseResult<_>>()?),
+        ),
+        ast::Expr::InSubquery {
+            expr,
+            subquery,
+            negated: false,
+        } => (
+            identifier(*expr)?,
+            Equation::InSubquery(query_to_select(*subquery)?),
+        ),
+        ast::Expr::Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        } => (
+            identifier(*expr)?,
+            Equation::Between((value(*low)?, value(*high)?)),
+        ),
+        ast::Expr::BinaryOp { left, op, right } => {
+            let column = identifier(*left)?;
+            let equation = match op {
+                ast::BinaryOperator::Like => Equation::Like(string(*right)?),
+                ast::BinaryOperator::ILike => Equation::ILike(string(*right)?),
+                ast::BinaryOperator::PGRegexMatch => Equation::Regex(string(*right)?),
+                op => comparison(op, *right)?,
+            };
+            (column, equation)
+        }
+        e => return unsupported(e),
+    };
+
+    Ok(Condition {
+        column,
+        equation,
+        mode: None,
+    })
+}
+
+fn comparison(op: ast::BinaryOperator, right: ast::Expr) -> ParseResult<Equation> {
+    let cmp = match op {
+        ast::BinaryOperator::Eq => Comparison::Equal,
+        ast::BinaryOperator::NotEq => Comparison::NotEqual,
+        ast::BinaryOperator::Gt => Comparison::Greater,
+        ast::BinaryOperator::GtEq => Comparison::GreaterEqual,
+        ast::BinaryOperator::Lt => Comparison::Less,
+        ast::BinaryOperator::LtEq => Comparison::LessEqual,
+        op => return unsupported(op),
+    };
+
+    let operand = match right {
+        ast::Expr::Identifier(i) => ColumnOperand {
+            column: i.value,
+            arithmetic: None,
+        },
+        ast::Expr::BinaryOp { left, op, right } if matches!(*left, ast::Expr::Identifier(_)) => {
+            let arithmetic = match op {
+                ast::BinaryOperator::Plus => Arithmetic::Add,
+                ast::BinaryOperator::Minus => Arithmetic::Sub,
+                ast::BinaryOperator::Multiply => Arithmetic::Mul,
+                ast::BinaryOperator::Divide => Arithmetic::Div,
+                op => return unsupported(op),
+            };
+            ColumnOperand {
+                column: identifier(*left)?,
+                arithmetic: Some((arithmetic, value(*right)?)),
+            }
+        }
+        e => {
+            let v = value(e)?;
+            return Ok(match cmp {
+                Comparison::Equal => Equation::Equal(v),
+                Comparison::NotEqual => Equation::NotEqual(v),
+                Comparison::Greater => Equation::Greater(v),
+                Comparison::GreaterEqual => Equation::GreaterEqual(v),
+                Comparison::Less => Equation::Less(v),
+                Comparison::LessEqual => Equation::LessEqual(v),
+            });
+        }
+    };
+
+    Ok(Equation::Column((cmp, operand)))
+}
+
+#[cfg(test)]
+mod tests_parser {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let sql = "SELECT c1, c2 AS c2_t FROM sqlz \
+            WHERE c1 BETWEEN 23 AND 25 OR c2 = 1 AND (c3 > 23 AND c4 IN ('T1', 'T2')) \
+            LIMIT 10 OFFSET 20";
+
+        let expected = r#"{"table":"sqlz","columns":["c1",["c2","c2_t"]],"filter":[{"column":"c1","equation":{"Between":[23,25]}},"OR",{"column":"c2","equation":{"Equal":1}},"AND",[{"column":"c3","equation":{"Greater":23}},"AND",{"column":"c4","equation":{"In":["T1","T2"]}}]],"order":null,"limit":10,"offset":20}"#;
+
+        let selection = parse_select(sql).unwrap();
+
+        assert_eq!(serde_json::to_string(&selection).unwrap(), expected);
+
+        let negative =
+            parse_select("SELECT a FROM t WHERE a = -9223372036854775808 OR b IN (-1.5, -2)")
+                .unwrap();
+        let equations: Vec<&Equation> = negative
+            .filter
+            .as_ref()
+            .unwrap()
+            .iter()
+            .filter_map(|e| match e {
+                Expression::Simple(c) => Some(&c.equation),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            equations,
+            vec![
+                &Equation::Equal(DataEnum::Integer(i64::MIN)),
+                &Equation::In(vec![DataEnum::Float(-1.5), DataEnum::Integer(-2)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_cte_and_projections() {
+        let sql = "WITH RECURSIVE tree (id) AS ( \
+                SELECT id FROM category WHERE parent_id IS NULL \
+                UNION ALL SELECT id FROM tree \
+            ) \
+            SELECT id, COALESCE(name, 'n/a') AS name, \
+                ROW_NUMBER() OVER (PARTITION BY kind ORDER BY id DESC) AS rn \
+            FROM tree \
+            WHERE updated_at > created_at + 1 AND NOT EXISTS (SELECT id FROM hidden) \
+            ORDER BY id";
+
+        let selection = parse_select(sql).unwrap();
+
+        let with = selection.with.as_ref().unwrap();
+        assert!(with.recursive);
+        assert_eq!(with.ctes[0].columns, Some(vec!["id".to_owned()]));
+        match &with.ctes[0].query {
+            CteQuery::Compound(c) => assert_eq!(c.compounds[0].0, SetOperator::UnionAll),
+            q => panic!("unexpected cte query {:?}", q),
+        }
+
+        assert_eq!(selection.columns.len(), 3);
+        match &selection.columns[2] {
+            ColumnAlias::Window(w) => {
+                assert_eq!(w.func, WindowFunction::RowNumber);
+                assert_eq!(w.partition_by, vec!["kind".to_owned()]);
+                assert_eq!(w.order_by[0].order, Some(OrderType::Desc));
+            }
+            c => panic!("unexpected column {:?}", c),
+        }
+
+        let filter = selection.filter.as_ref().unwrap();
+        assert_eq!(
+            filter[0],
+            Expression::Simple(Condition {
+                column: "updated_at".to_owned(),
+                equation: Equation::Column((
+                    Comparison::Greater,
+                    ColumnOperand {
+                        column: "created_at".to_owned(),
+                        arithmetic: Some((Arithmetic::Add, DataEnum::Integer(1))),
+                    }
+                )),
+                mode: None,
+            })
+        );
+        assert!(matches!(&filter[2], Expression::Exists(e) if e.negated));
+
+        // the lineage of a parsed select traces back through the cte
+        assert_eq!(selection.lineage()[0].sources[0].table, "category");
+    }
+
+    #[test]
+    fn normalization() {
+        let a =
+            "select ID, name from \"Users\"  -- comment\n where id in (1, 2, 3) and active = true;";
+        let b = "SELECT id, NAME FROM \"Users\" WHERE ID IN ('a') AND active = false";
+
+        assert_eq!(
+            normalize(a).unwrap(),
+            "SELECT id , name FROM \"Users\" WHERE id IN ( ? ) AND active = ?"
+        );
+        assert_eq!(fingerprint(a).unwrap(), fingerprint(b).unwrap());
+        assert_ne!(
+            fingerprint(a).unwrap(),
+            fingerprint("SELECT id FROM \"Users\"").unwrap()
+        );
+
+        assert_eq!(
+            normalize("SELECT a - 1 FROM t WHERE x = -1 AND y IN (1, -2, 3)").unwrap(),
+            "SELECT a - ? FROM t WHERE x = ? AND y IN ( ? )"
+        );
+        assert_eq!(
+            fingerprint("SELECT * FROM t WHERE x = -1").unwrap(),
+            fingerprint("SELECT * FROM t WHERE x = 1").unwrap()
+        );
+
+        // only lists of `IN` and `VALUES` collapse
+        assert_ne!(
+            fingerprint("SELECT 1, 2 FROM t").unwrap(),
+            fingerprint("SELECT 1 FROM t").unwrap()
+        );
+        assert_eq!(
+            normalize("INSERT INTO t VALUES (1, 'a'), (2, 'b')").unwrap(),
+            "INSERT INTO t VALUES ( ? ) , ( ? )"
+        );
+        assert_eq!(
+            normalize("SELECT COALESCE(a, 1, 2) FROM t").unwrap(),
+            "SELECT COALESCE ( a , ? , ? ) FROM t"
+        );
+
+        // placeholders are kept as they are
+        assert_eq!(
+            normalize("SELECT a FROM t WHERE a = $1 AND b IN ($2, 3)").unwrap(),
+            "SELECT a FROM t WHERE a = $1 AND b IN ( $2 , ? )"
+        );
+    }
+
+    #[test]
+    fn parse_unsupported() {
+        assert!(matches!(
+            parse_select("SELECT a FROM t1 JOIN t2 ON t1.id = t2.id"),
+            Err(ParseError::Unsupported(_))
+        ));
+        assert!(matches!(
+            parse_select("SELECT a, COUNT(*) FROM t GROUP BY a"),
+            Err(ParseError::Unsupported(_))
+        ));
+        assert!(matches!(
+            parse_select("(SELECT a FROM t) OFFSET 5"),
+            Err(ParseError::Unsupported(_))
+        ));
+        assert!(matches!(
+            parse_select("SELECT LOWER(a), LOWER(b) FROM t"),
+            Err(ParseError::Unsupported(_))
+        ));
+        assert!(parse_select("SELECT LOWER(a), LOWER(b) AS lower_b FROM t").is_ok());
+        assert!(matches!(
+            parse_select("SELEC a FROM t"),
+            Err(ParseError::Syntax(_))
+        ));
+    }
+}